@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use color_eyre::{eyre::Report, Result};
 
@@ -9,14 +10,22 @@ use teloxide::{
     respond,
     types::{
         CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, InlineQuery, InlineQueryResult,
-        InlineQueryResultArticle, InputMessageContent, InputMessageContentText, Message, Update,
+        InlineQueryResultArticle, InputMessageContent, InputMessageContentText, Update,
     },
     Bot,
 };
 
 use teloxide::prelude::*;
 
-use crate::{callback_data::CallbackData, concurrent_hash_map::ConcurrentHashMap, game::Game};
+use crate::{
+    callback_data::CallbackData,
+    concurrent_hash_map::ConcurrentHashMap,
+    game::{Game, MessageLocation},
+    game_store::{FileGameStore, GameStore},
+};
+
+// How often the background sweep checks for expired turns and idle games.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
 
 async fn schema() -> UpdateHandler<Report> {
     dptree::entry()
@@ -25,16 +34,27 @@ async fn schema() -> UpdateHandler<Report> {
 }
 
 async fn inline_handler(bot: Bot, q: InlineQuery) -> Result<(), Report> {
-    let button = InlineKeyboardButton::callback("Create a game", CallbackData::Join.to_string());
+    // (board size, win length) presets, one button per board size.
+    const PRESETS: [(usize, usize); 4] = [(3, 3), (4, 3), (5, 4), (6, 4)];
+
+    let buttons: Vec<InlineKeyboardButton> = PRESETS
+        .into_iter()
+        .map(|(size, k)| {
+            InlineKeyboardButton::callback(
+                format!("{size}x{size}"),
+                CallbackData::Create { size, k }.to_string(),
+            )
+        })
+        .collect();
 
     let article = InlineQueryResultArticle::new(
         "play".to_string(),
         "Play",
         InputMessageContent::Text(InputMessageContentText::new(
-            "TikTakToe\n\nPress a button to create a game",
+            "TikTakToe\n\nPick a board size to create a game",
         )),
     )
-    .reply_markup(InlineKeyboardMarkup::new(vec![vec![button]]));
+    .reply_markup(InlineKeyboardMarkup::new(vec![buttons]));
 
     let results = vec![InlineQueryResult::Article(article)];
 
@@ -45,19 +65,26 @@ async fn inline_handler(bot: Bot, q: InlineQuery) -> Result<(), Report> {
     Ok(())
 }
 
-pub async fn update_message(bot: Bot, q: CallbackQuery, game: &Game) -> Result<(), Report> {
-    let (text, keyboard) = game.as_message();
-
-    bot.answer_callback_query(&q.id).await?;
-
-    if let Some(Message { id, chat, .. }) = q.message {
-        bot.edit_message_text(chat.id, id, text)
-            .reply_markup(keyboard)
-            .await?;
-    } else if let Some(id) = q.inline_message_id {
-        bot.edit_message_text_inline(id, text)
-            .reply_markup(keyboard)
-            .await?;
+pub async fn update_message(
+    bot: &Bot,
+    location: &MessageLocation,
+    text: String,
+    keyboard: InlineKeyboardMarkup,
+) -> Result<(), Report> {
+    match location {
+        MessageLocation::Chat {
+            chat_id,
+            message_id,
+        } => {
+            bot.edit_message_text(*chat_id, *message_id, text)
+                .reply_markup(keyboard)
+                .await?;
+        }
+        MessageLocation::Inline(id) => {
+            bot.edit_message_text_inline(id, text)
+                .reply_markup(keyboard)
+                .await?;
+        }
     }
 
     Ok(())
@@ -67,15 +94,32 @@ pub async fn callback_handler(
     bot: Bot,
     q: CallbackQuery,
     storage: Arc<ConcurrentHashMap<String, Game>>,
+    store: Arc<dyn GameStore>,
 ) -> Result<(), Report> {
-    let state = storage
-        .get_or_default(q.inline_message_id.as_ref().unwrap())
-        .await;
+    let key = q.inline_message_id.clone().unwrap();
+    let state = storage.get_or_default(&key).await;
 
     let mut lock = state.lock().await;
 
     match lock.process_callback(q.clone()) {
-        Ok(_) => update_message(bot, q, &lock).await?,
+        Ok(_) => {
+            if let Some(location) = MessageLocation::from_callback_query(&q) {
+                lock.set_message_location(location);
+            }
+
+            bot.answer_callback_query(&q.id).await?;
+
+            if let Some(location) = lock.message_location().cloned() {
+                if let Some((text, keyboard, hash)) = lock.pending_render() {
+                    update_message(&bot, &location, text, keyboard).await?;
+                    lock.mark_rendered(hash);
+                }
+            }
+
+            if let Err(error) = store.save(&key, &lock).await {
+                log::warn!("Failed to persist game {key}: {error}");
+            }
+        }
         Err(error) => {
             bot.answer_callback_query(q.id)
                 .text(error.to_string())
@@ -87,16 +131,75 @@ pub async fn callback_handler(
     Ok(())
 }
 
+async fn sweep_games(bot: &Bot, storage: &ConcurrentHashMap<String, Game>, store: &dyn GameStore) {
+    let mut expired_keys = Vec::new();
+
+    for (key, state) in storage.entries().await {
+        let mut game = state.lock().await;
+
+        if game.is_turn_expired() && game.forfeit_turn() {
+            if let Some(location) = game.message_location().cloned() {
+                if let Some((text, keyboard, hash)) = game.pending_render() {
+                    match update_message(bot, &location, text, keyboard).await {
+                        Ok(()) => game.mark_rendered(hash),
+                        Err(error) => log::warn!("Failed to edit expired game message: {error}"),
+                    }
+                }
+            }
+
+            if let Err(error) = store.save(&key, &game).await {
+                log::warn!("Failed to persist forfeited game {key}: {error}");
+            }
+        }
+
+        // `last_activity` is refreshed whenever a game finishes (see `forfeit_turn`
+        // and `process_callback`), so this also gives a finished game a grace period
+        // to be restarted before its scoreboard is swept away.
+        if game.is_idle_expired() {
+            expired_keys.push(key);
+        }
+    }
+
+    for key in expired_keys {
+        storage.remove(&key).await;
+        if let Err(error) = store.remove(&key).await {
+            log::warn!("Failed to delete persisted game {key}: {error}");
+        }
+    }
+}
+
 pub async fn bot_main() -> Result<()> {
     log::info!("Starting bot...");
 
     let bot = Bot::from_env();
 
+    let store: Arc<dyn GameStore> = Arc::new(FileGameStore::new("games"));
     let state_storage = Arc::new(ConcurrentHashMap::<String, Game>::new());
 
+    // `turn_deadline` isn't persisted, so any game loaded mid-turn needs a fresh
+    // deadline or its timeout would never fire until the next move.
+    let mut loaded_games = store.load_all().await?;
+    for game in loaded_games.values_mut() {
+        game.rearm_turn_deadline();
+    }
+    state_storage.extend(loaded_games).await;
+
+    tokio::spawn({
+        let bot = bot.clone();
+        let state_storage = state_storage.clone();
+        let store = store.clone();
+        async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                sweep_games(&bot, &state_storage, store.as_ref()).await;
+            }
+        }
+    });
+
     Dispatcher::builder(bot, schema().await)
         .enable_ctrlc_handler()
-        .dependencies(dptree::deps![state_storage])
+        .dependencies(dptree::deps![state_storage, store])
         .build()
         .dispatch()
         .await;
@@ -1,10 +1,14 @@
 use std::{fmt::Display, str::FromStr};
 
-use crate::game::GameError;
+use crate::game::{AIDifficulty, GameError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CallbackData {
+    Create { size: usize, k: usize },
     Join,
+    Accept,
+    Decline,
+    PlayBot { difficulty: AIDifficulty },
     Restart,
     Place { x: usize, y: usize },
     Unknown,
@@ -14,7 +18,11 @@ impl Display for CallbackData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(
             match self {
+                CallbackData::Create { size, k } => format!("create:{size}:{k}"),
                 CallbackData::Join => "join".to_string(),
+                CallbackData::Accept => "accept".to_string(),
+                CallbackData::Decline => "decline".to_string(),
+                CallbackData::PlayBot { difficulty } => format!("bot:{difficulty}"),
                 CallbackData::Place { x, y } => format!("place:{x}:{y}"),
                 CallbackData::Unknown => "unknown".to_string(),
                 CallbackData::Restart => "restart".to_string(),
@@ -30,6 +38,24 @@ impl FromStr for CallbackData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let value = match s {
             "join" => Self::Join,
+            "accept" => Self::Accept,
+            "decline" => Self::Decline,
+            s if s.starts_with("create:") => {
+                let mut split = s.split(":").skip(1);
+                let mut process_split = || {
+                    split
+                        .next()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .ok_or(GameError::UnknownCommand)
+                };
+                Self::Create {
+                    size: process_split()?,
+                    k: process_split()?,
+                }
+            }
+            s if s.starts_with("bot:") => Self::PlayBot {
+                difficulty: s.split(":").nth(1).ok_or(GameError::UnknownCommand)?.parse()?,
+            },
             s if s.starts_with("place") => {
                 let mut split = s.split(":").skip(1);
                 let mut process_split = || {
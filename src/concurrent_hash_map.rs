@@ -18,6 +18,24 @@ where
         let lock = self.read().await;
         lock.get(key).cloned()
     }
+
+    pub async fn entries(&self) -> Vec<(K, Arc<Mutex<V>>)>
+    where
+        K: Clone,
+    {
+        let lock = self.read().await;
+        lock.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+}
+
+impl<K, V> ConcurrentHashMap<K, V>
+where
+    K: std::hash::Hash + PartialEq + Eq + Clone,
+{
+    pub async fn remove(&self, key: &K) -> Option<Arc<Mutex<V>>> {
+        let mut lock = self.write().await;
+        lock.remove(key)
+    }
 }
 
 impl<K, V> ConcurrentHashMap<K, V>
@@ -36,4 +54,11 @@ where
             }
         }
     }
+
+    pub async fn extend(&self, entries: impl IntoIterator<Item = (K, V)>) {
+        let mut lock = self.write().await;
+        for (key, value) in entries {
+            lock.insert(key, Arc::new(Mutex::new(value)));
+        }
+    }
 }
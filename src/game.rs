@@ -1,36 +1,70 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 use color_eyre::eyre::Result;
 use itertools::Itertools;
-use shrinkwraprs::Shrinkwrap;
-use teloxide::types::{CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, User};
+use rand::{seq::IteratorRandom, Rng};
+use serde::{Deserialize, Serialize};
+use teloxide::types::{
+    CallbackQuery, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, Message, MessageId, User,
+    UserId,
+};
 
 use crate::callback_data::CallbackData;
 
 // use itertools::Itertools;
 
-const BOARD_SIZE: usize = 3;
+pub const DEFAULT_BOARD_SIZE: usize = 3;
+pub const DEFAULT_WIN_LENGTH: usize = 3;
 
-const MAX_INDEX: usize = BOARD_SIZE - 1;
+// Telegram inline keyboards cap out at 8 buttons per row and 100 buttons total,
+// so an 8x8 board (64 cells) is the largest grid we can still render as one.
+pub const MIN_BOARD_SIZE: usize = 3;
+pub const MAX_BOARD_SIZE: usize = 8;
 
-const WIN_CONDITIONS: [[BoardIndex; BOARD_SIZE]; (BOARD_SIZE * 2) + 2] = [
-    [BoardIndex(0, 0), BoardIndex(0, 1), BoardIndex(0, 2)],
-    [BoardIndex(1, 0), BoardIndex(1, 1), BoardIndex(1, 2)],
-    [BoardIndex(2, 0), BoardIndex(2, 1), BoardIndex(2, 2)],
-    [BoardIndex(0, 1), BoardIndex(1, 1), BoardIndex(2, 1)],
-    [BoardIndex(0, 0), BoardIndex(1, 0), BoardIndex(2, 0)],
-    [BoardIndex(0, 2), BoardIndex(1, 2), BoardIndex(2, 2)],
-    [BoardIndex(0, 0), BoardIndex(1, 1), BoardIndex(2, 2)],
-    [BoardIndex(2, 0), BoardIndex(1, 1), BoardIndex(0, 2)],
-];
+// `minimax` is a plain exhaustive search with no pruning, so it only stays fast
+// enough to run inside the game's lock on boards this small or smaller.
+pub const AI_MAX_CELLS: usize = 9;
 
-#[derive(Shrinkwrap)]
-#[shrinkwrap(mutable)]
-pub struct Board(pub [[Option<Shape>; BOARD_SIZE]; BOARD_SIZE]);
+// How long a player has to make a move before they forfeit the game.
+pub const TURN_TIMEOUT: Duration = Duration::from_secs(60);
+// How long a game can sit untouched before it's swept out of the registry.
+pub const GAME_IDLE_TTL: Duration = Duration::from_secs(60 * 30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MessageLocation {
+    Chat { chat_id: ChatId, message_id: MessageId },
+    Inline(String),
+}
+
+impl MessageLocation {
+    pub fn from_callback_query(q: &CallbackQuery) -> Option<Self> {
+        if let Some(Message { id, chat, .. }) = &q.message {
+            Some(Self::Chat {
+                chat_id: chat.id,
+                message_id: *id,
+            })
+        } else {
+            q.inline_message_id.clone().map(Self::Inline)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Board {
+    cells: Vec<Vec<Option<Shape>>>,
+    size: usize,
+    k: usize,
+}
 
 impl Board {
     pub fn as_buttons(&self) -> InlineKeyboardMarkup {
         let buttons: Vec<Vec<InlineKeyboardButton>> = self
+            .cells
             .iter()
             .enumerate()
             .map(|(y, row)| {
@@ -54,11 +88,12 @@ impl Board {
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct BoardIndex(pub usize, pub usize);
 
 impl BoardIndex {
-    fn new(x: usize, y: usize) -> Result<Self, GameError> {
-        if x > MAX_INDEX || y > MAX_INDEX {
+    fn new(x: usize, y: usize, size: usize) -> Result<Self, GameError> {
+        if x >= size || y >= size {
             return Err(GameError::OutOfBounds);
         }
         Ok(Self(x, y))
@@ -75,6 +110,7 @@ pub enum GameError {
     Permission,
     NoData,
     UnknownCommand,
+    BoardTooLargeForAi,
 }
 
 impl Display for GameError {
@@ -89,18 +125,60 @@ impl Display for GameError {
             GameError::UnknownCommand => {
                 "Command doesn't exist or is not appliable to current state"
             }
+            GameError::BoardTooLargeForAi => "The bot can only play on boards up to 3x3",
         })
     }
 }
 
+// Every cell paired with each of the four line directions (right, down,
+// down-right, down-left) gives every run of `k` cells on the board exactly once.
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+
 impl Board {
-    pub fn empty() -> Self {
-        Self([[None; BOARD_SIZE]; BOARD_SIZE])
+    pub fn empty(size: usize, k: usize) -> Self {
+        let size = size.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        let k = k.clamp(1, size);
+        Self {
+            cells: vec![vec![None; size]; size],
+            size,
+            k,
+        }
     }
 
-    pub fn check_win_condition(&self, condition: &[BoardIndex; BOARD_SIZE]) -> Option<Shape> {
-        condition
-            .iter()
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.size * self.size
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn validate_index(&self, x: usize, y: usize) -> Result<BoardIndex, GameError> {
+        BoardIndex::new(x, y, self.size)
+    }
+
+    fn win_lines(&self) -> impl Iterator<Item = Vec<BoardIndex>> + '_ {
+        let size = self.size as isize;
+        (0..size)
+            .flat_map(move |y| (0..size).map(move |x| (x, y)))
+            .flat_map(move |(x, y)| DIRECTIONS.iter().map(move |&direction| (x, y, direction)))
+            .filter_map(move |(x, y, (dx, dy))| {
+                (0..self.k as isize)
+                    .map(|step| {
+                        let (nx, ny) = (x + dx * step, y + dy * step);
+                        (nx >= 0 && nx < size && ny >= 0 && ny < size)
+                            .then_some(BoardIndex(nx as usize, ny as usize))
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+    }
+
+    pub fn check_win_line(&self, line: &[BoardIndex]) -> Option<Shape> {
+        line.iter()
             .map(|index| self.get_ref(index))
             .reduce(|a, b| {
                 let a = a?;
@@ -111,31 +189,41 @@ impl Board {
     }
 
     pub fn check_winner(&self) -> Option<Shape> {
-        WIN_CONDITIONS
-            .iter()
-            .find_map(|condition| self.check_win_condition(condition))
+        self.win_lines()
+            .find_map(|line| self.check_win_line(&line))
     }
 
     pub fn check_draw(&self) -> bool {
-        self.iter().flatten().all(|cell| cell.is_some())
+        self.cells.iter().flatten().all(|cell| cell.is_some())
     }
 
     pub fn get_ref(&self, index: &BoardIndex) -> Option<&Shape> {
-        self[index.1][index.0].as_ref()
+        self.cells[index.1][index.0].as_ref()
     }
 
     pub fn set_cell(&mut self, index: &BoardIndex, shape: Shape) {
-        self[index.1][index.0] = Some(shape)
+        self.cells[index.1][index.0] = Some(shape)
+    }
+
+    pub fn clear_cell(&mut self, index: &BoardIndex) {
+        self.cells[index.1][index.0] = None
+    }
+
+    pub fn empty_indices(&self) -> Vec<BoardIndex> {
+        (0..self.size)
+            .flat_map(|y| (0..self.size).map(move |x| BoardIndex(x, y)))
+            .filter(|index| self.get_ref(index).is_none())
+            .collect()
     }
 }
 
 impl Default for Board {
     fn default() -> Self {
-        Board::empty()
+        Board::empty(DEFAULT_BOARD_SIZE, DEFAULT_WIN_LENGTH)
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum Shape {
     #[default]
     X,
@@ -153,11 +241,94 @@ impl Display for Shape {
 
 static SHAPES: [Shape; 2] = [Shape::X, Shape::O];
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AIDifficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Display for AIDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AIDifficulty::Easy => "easy",
+            AIDifficulty::Medium => "medium",
+            AIDifficulty::Hard => "hard",
+        })
+    }
+}
+
+impl FromStr for AIDifficulty {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            _ => Err(GameError::UnknownCommand),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    Multiplayer,
+    SinglePlayer(Shape, AIDifficulty),
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Multiplayer
+    }
+}
+
+/// The handful of fields of a Telegram `User` we actually need, cached so a
+/// `Game` can be serialized without depending on `teloxide::types::User`'s own
+/// (de)serialization support.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl From<&User> for PlayerInfo {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            name: user.full_name(),
+        }
+    }
+}
+
+impl PartialEq for PlayerInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for PlayerInfo {}
+
+impl std::hash::Hash for PlayerInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Game {
     board: Board,
-    players: HashMap<Shape, User>,
-    score: HashMap<User, usize>,
+    players: HashMap<Shape, PlayerInfo>,
+    score: HashMap<PlayerInfo, usize>,
     state: GameState,
+    mode: GameMode,
+    message_location: Option<MessageLocation>,
+    #[serde(skip, default = "Instant::now")]
+    last_activity: Instant,
+    #[serde(skip)]
+    turn_deadline: Option<Instant>,
+    #[serde(skip)]
+    rendered_hash: Option<u64>,
 }
 
 impl Default for Game {
@@ -167,6 +338,11 @@ impl Default for Game {
             players: HashMap::new(),
             score: HashMap::new(),
             state: GameState::Waiting,
+            mode: GameMode::default(),
+            message_location: None,
+            last_activity: Instant::now(),
+            turn_deadline: None,
+            rendered_hash: None,
         }
     }
 }
@@ -175,7 +351,7 @@ impl Game {
     pub fn player_name(&self, shape: &Shape) -> String {
         self.players
             .get(&shape)
-            .and_then(|player| Some(player.full_name()))
+            .map(|player| player.name.clone())
             .unwrap_or(shape.to_string())
     }
 
@@ -184,7 +360,7 @@ impl Game {
             self.score
                 .iter()
                 .sorted_by_key(|(_, score)| *score)
-                .map(|(user, score)| format!("{}: {score}", user.full_name()))
+                .map(|(player, score)| format!("{}: {score}", player.name))
                 .rev(),
             String::from("\n"),
         )
@@ -203,20 +379,63 @@ impl Game {
     pub fn as_message(&self) -> (String, InlineKeyboardMarkup) {
         let text = match &self.state {
             GameState::Waiting => match self.players.values().next() {
-                Some(user) => format!("{} is waiting for the opponent", user.full_name()),
+                Some(player) => format!("{} is waiting for the opponent", player.name),
                 None => format!("Waiting for players"),
             },
+            GameState::PendingAccept { challenger } => {
+                let proposer = self
+                    .players
+                    .values()
+                    .next()
+                    .map(|player| player.name.as_str())
+                    .unwrap_or("The host");
+                format!("{} wants to play against {proposer}", challenger.name)
+            }
             GameState::Turn(shape) => format!("{} {}'s turn", shape, self.player_name(&shape)),
             GameState::Finished(result) => self.finished_text(result),
         };
 
         let keyboard = match &self.state {
             GameState::Waiting => {
-                InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+                let mut rows = vec![vec![InlineKeyboardButton::callback(
                     "Join",
                     CallbackData::Join.to_string(),
-                )]])
+                )]];
+
+                // The bot plays via exhaustive minimax with no pruning, so it's only
+                // offered on boards small enough to search in a single request.
+                if self.board.cell_count() <= AI_MAX_CELLS {
+                    rows.push(vec![
+                        InlineKeyboardButton::callback(
+                            "Play vs bot (Easy)",
+                            CallbackData::PlayBot {
+                                difficulty: AIDifficulty::Easy,
+                            }
+                            .to_string(),
+                        ),
+                        InlineKeyboardButton::callback(
+                            "Play vs bot (Medium)",
+                            CallbackData::PlayBot {
+                                difficulty: AIDifficulty::Medium,
+                            }
+                            .to_string(),
+                        ),
+                        InlineKeyboardButton::callback(
+                            "Play vs bot (Hard)",
+                            CallbackData::PlayBot {
+                                difficulty: AIDifficulty::Hard,
+                            }
+                            .to_string(),
+                        ),
+                    ]);
+                }
+
+                InlineKeyboardMarkup::new(rows)
             }
+            GameState::PendingAccept { .. } => InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("Accept", CallbackData::Accept.to_string()),
+                InlineKeyboardButton::callback("Decline", CallbackData::Decline.to_string()),
+            ]]),
             GameState::Turn(_) => self.get_board(),
             GameState::Finished(_) => {
                 self.get_board()
@@ -230,6 +449,36 @@ impl Game {
         (text, keyboard)
     }
 
+    /// Renders the game and returns the text/keyboard/fingerprint only if they differ
+    /// from the last render actually delivered, so callers can skip a no-op Telegram
+    /// edit (which would otherwise fail with "message is not modified"). Pass the
+    /// fingerprint to `mark_rendered` once the edit has been sent successfully.
+    pub fn pending_render(&self) -> Option<(String, InlineKeyboardMarkup, u64)> {
+        let (text, keyboard) = self.as_message();
+        let hash = Self::fingerprint(&text, &keyboard);
+
+        if self.rendered_hash == Some(hash) {
+            return None;
+        }
+
+        Some((text, keyboard, hash))
+    }
+
+    /// Records that the render identified by `hash` has been delivered, so a later
+    /// no-op render can be skipped.
+    pub fn mark_rendered(&mut self, hash: u64) {
+        self.rendered_hash = Some(hash);
+    }
+
+    fn fingerprint(text: &str, keyboard: &InlineKeyboardMarkup) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        text.hash(&mut hasher);
+        format!("{keyboard:?}").hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn add_user(&mut self, user: User) -> Result<(), GameError> {
         let shape = SHAPES
             .iter()
@@ -241,18 +490,77 @@ impl Game {
             return Err(GameError::Permission);
         }
 
-        self.score.insert(user.clone(), Default::default());
-        self.players.insert(shape, user);
+        let info = PlayerInfo::from(&user);
+        self.score.insert(info.clone(), Default::default());
+        self.players.insert(shape, info);
 
         Ok(())
     }
 
     pub fn process_callback(&mut self, q: CallbackQuery) -> Result<(), GameError> {
-        match self.state {
+        let result = match self.state {
             GameState::Waiting => self.process_callback_waiting(q),
+            GameState::PendingAccept { .. } => self.process_callback_pending_accept(q),
             GameState::Turn(turn) => self.process_callback_turn(turn, q),
             GameState::Finished(_) => self.process_callback_finished(q),
+        };
+
+        if result.is_ok() {
+            self.last_activity = Instant::now();
         }
+
+        result
+    }
+
+    pub fn message_location(&self) -> Option<&MessageLocation> {
+        self.message_location.as_ref()
+    }
+
+    pub fn set_message_location(&mut self, location: MessageLocation) {
+        if self.message_location.is_none() {
+            self.message_location = Some(location);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, GameState::Finished(_))
+    }
+
+    pub fn is_idle_expired(&self) -> bool {
+        self.last_activity.elapsed() > GAME_IDLE_TTL
+    }
+
+    pub fn is_turn_expired(&self) -> bool {
+        self.turn_deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
+    /// `turn_deadline` isn't persisted, so a game reloaded from disk mid-`Turn`
+    /// would otherwise never time out. Call this once after loading to give it a
+    /// fresh deadline; a no-op for any other state.
+    pub fn rearm_turn_deadline(&mut self) {
+        if matches!(self.state, GameState::Turn(_)) {
+            self.turn_deadline = Some(Instant::now() + TURN_TIMEOUT);
+        }
+    }
+
+    /// Declares the player whose turn it is as forfeited, handing the win to their
+    /// opponent. Returns whether a forfeit actually happened, so the caller knows
+    /// whether to persist the game; use `pending_render` to get the update.
+    pub fn forfeit_turn(&mut self) -> bool {
+        let GameState::Turn(turn_shape) = self.state else {
+            return false;
+        };
+
+        let winner = other_shape(turn_shape);
+        if let Some(winner_user) = self.players.get(&winner) {
+            *self.score.entry(winner_user.clone()).or_insert(0) += 1;
+        }
+        self.state = GameState::Finished(GameResult::Victory { winner });
+        self.turn_deadline = None;
+        self.last_activity = Instant::now();
+
+        true
     }
 
     pub fn get_board(&self) -> InlineKeyboardMarkup {
@@ -260,14 +568,94 @@ impl Game {
     }
 
     fn process_callback_waiting(&mut self, q: CallbackQuery) -> Result<(), GameError> {
-        if q.data.ok_or(GameError::NoData)?.parse::<CallbackData>()? != CallbackData::Join {
-            return Err(GameError::UnknownCommand);
+        match q.data.ok_or(GameError::NoData)?.parse::<CallbackData>()? {
+            CallbackData::Create { size, k } => {
+                self.board = Board::empty(size, k);
+                self.add_user(q.from)?;
+            }
+            CallbackData::Join => {
+                if self.players.values().any(|value| value.id == q.from.id) {
+                    return Err(GameError::Permission);
+                }
+
+                // No host to ask for acceptance yet (e.g. a stale pre-upgrade "join"
+                // button on a fresh game) — just seat the player directly.
+                if self.players.is_empty() {
+                    self.add_user(q.from)?;
+                } else {
+                    self.state = GameState::PendingAccept {
+                        challenger: PlayerInfo::from(&q.from),
+                    };
+                }
+            }
+            CallbackData::PlayBot { difficulty } => {
+                if self.board.cell_count() > AI_MAX_CELLS {
+                    return Err(GameError::BoardTooLargeForAi);
+                }
+
+                // Whoever created the game is already seated by the time this button
+                // is visible — don't re-seat them, and don't let a second human hijack
+                // someone else's game into bot mode.
+                if self.players.is_empty() {
+                    self.add_user(q.from)?;
+                } else if !self.players.values().any(|player| player.id == q.from.id) {
+                    return Err(GameError::Permission);
+                }
+
+                let ai_shape = SHAPES
+                    .iter()
+                    .find(|shape| !self.players.contains_key(shape))
+                    .copied()
+                    .ok_or(GameError::IllegalState)?;
+
+                self.mode = GameMode::SinglePlayer(ai_shape, difficulty);
+                self.state = GameState::Turn(Shape::X);
+                self.turn_deadline = Some(Instant::now() + TURN_TIMEOUT);
+                self.board = Board::empty(self.board.size(), self.board.k());
+                self.maybe_play_ai_turn();
+            }
+            _ => return Err(GameError::UnknownCommand),
         }
-        self.add_user(q.from)?;
-        if self.players.len() >= 2 {
-            self.state = GameState::Turn(Shape::X);
-            self.board = Board::empty();
+        Ok(())
+    }
+
+    fn process_callback_pending_accept(&mut self, q: CallbackQuery) -> Result<(), GameError> {
+        let challenger = match &self.state {
+            GameState::PendingAccept { challenger } => challenger.clone(),
+            _ => return Err(GameError::IllegalState),
+        };
+
+        let proposer_id = self
+            .players
+            .values()
+            .next()
+            .ok_or(GameError::IllegalState)?
+            .id;
+
+        if q.from.id != proposer_id {
+            return Err(GameError::Permission);
+        }
+
+        match q.data.ok_or(GameError::NoData)?.parse::<CallbackData>()? {
+            CallbackData::Accept => {
+                let shape = SHAPES
+                    .iter()
+                    .find(|shape| !self.players.contains_key(shape))
+                    .copied()
+                    .ok_or(GameError::IllegalState)?;
+
+                self.score.insert(challenger.clone(), Default::default());
+                self.players.insert(shape, challenger);
+                self.board = Board::empty(self.board.size(), self.board.k());
+                self.state = GameState::Turn(Shape::X);
+                self.turn_deadline = Some(Instant::now() + TURN_TIMEOUT);
+            }
+            CallbackData::Decline => {
+                self.state = GameState::Waiting;
+            }
+            _ => return Err(GameError::UnknownCommand),
         }
+
         Ok(())
     }
 
@@ -300,7 +688,7 @@ impl Game {
         }
 
         let index = match q.data.ok_or(GameError::NoData)?.parse::<CallbackData>()? {
-            CallbackData::Place { x, y } => BoardIndex::new(x, y),
+            CallbackData::Place { x, y } => self.board.validate_index(x, y),
             _ => Err(GameError::IllegalState),
         }?;
 
@@ -309,32 +697,132 @@ impl Game {
         }
 
         self.board.set_cell(&index, turn_shape);
+        self.resolve_turn(turn_shape);
+        self.maybe_play_ai_turn();
+
+        Ok(())
+    }
 
+    fn resolve_turn(&mut self, turn_shape: Shape) {
         kiam::when! {
             let Some(winner) = self.board.check_winner() => {
                 if let Some(winner) = self.players.get(&winner) {
                     *self.score.entry(winner.clone()).or_insert(0) += 1;
                 }
+                self.turn_deadline = None;
                 self.state = GameState::Finished(GameResult::Victory { winner });
             },
             self.board.check_draw() => {
+                self.turn_deadline = None;
                 self.state = GameState::Finished(GameResult::Draw);
             },
             _ => {
-                self.state = GameState::Turn(match turn_shape {
-                    Shape::O => Shape::X,
-                    Shape::X => Shape::O,
-                });
+                self.state = GameState::Turn(other_shape(turn_shape));
+                self.turn_deadline = Some(Instant::now() + TURN_TIMEOUT);
             }
         }
+    }
 
-        Ok(())
+    fn maybe_play_ai_turn(&mut self) {
+        let GameState::Turn(turn_shape) = self.state else {
+            return;
+        };
+        let GameMode::SinglePlayer(ai_shape, difficulty) = self.mode else {
+            return;
+        };
+        if turn_shape != ai_shape {
+            return;
+        }
+
+        if let Some(index) = self.choose_ai_move(ai_shape, difficulty) {
+            self.board.set_cell(&index, ai_shape);
+            self.resolve_turn(ai_shape);
+        }
+    }
+
+    fn choose_ai_move(&mut self, ai_shape: Shape, difficulty: AIDifficulty) -> Option<BoardIndex> {
+        let empty = self.board.empty_indices();
+        if empty.is_empty() {
+            return None;
+        }
+
+        let play_optimally = match difficulty {
+            AIDifficulty::Easy => false,
+            AIDifficulty::Medium => rand::thread_rng().gen_bool(0.6),
+            AIDifficulty::Hard => true,
+        };
+
+        if play_optimally {
+            Self::best_move(&mut self.board, ai_shape)
+        } else {
+            empty.into_iter().choose(&mut rand::thread_rng())
+        }
+    }
+
+    fn best_move(board: &mut Board, ai_shape: Shape) -> Option<BoardIndex> {
+        board
+            .empty_indices()
+            .into_iter()
+            .map(|index| {
+                board.set_cell(&index, ai_shape);
+                let score = Self::minimax(board, ai_shape, other_shape(ai_shape), 1);
+                board.clear_cell(&index);
+                (index, score)
+            })
+            .max_by_key(|(_, score)| *score)
+            .map(|(index, _)| index)
+    }
+
+    fn minimax(board: &mut Board, ai_shape: Shape, turn_shape: Shape, depth: i32) -> i32 {
+        if let Some(winner) = board.check_winner() {
+            return if winner == ai_shape {
+                10 - depth
+            } else {
+                depth - 10
+            };
+        }
+
+        if board.check_draw() {
+            return 0;
+        }
+
+        let scores: Vec<i32> = board
+            .empty_indices()
+            .into_iter()
+            .map(|index| {
+                board.set_cell(&index, turn_shape);
+                let score = Self::minimax(board, ai_shape, other_shape(turn_shape), depth + 1);
+                board.clear_cell(&index);
+                score
+            })
+            .collect();
+
+        if turn_shape == ai_shape {
+            scores.into_iter().max().unwrap_or(0)
+        } else {
+            scores.into_iter().min().unwrap_or(0)
+        }
     }
 
     fn reset(&mut self) -> Result<(), GameError> {
-        self.swap_shapes()?;
-        self.board = Board::default();
+        match self.mode {
+            GameMode::Multiplayer => self.swap_shapes()?,
+            // There's no `PlayerInfo` for the AI's shape to swap with, so instead
+            // move the human to the other shape and let the AI take their old one.
+            GameMode::SinglePlayer(ai_shape, difficulty) => {
+                let human_shape = other_shape(ai_shape);
+                if let Some(human) = self.players.remove(&human_shape) {
+                    self.players.insert(ai_shape, human);
+                }
+                self.mode = GameMode::SinglePlayer(human_shape, difficulty);
+            }
+        }
+
+        self.board = Board::empty(self.board.size(), self.board.k());
         self.state = GameState::Turn(Shape::default());
+        self.turn_deadline = Some(Instant::now() + TURN_TIMEOUT);
+        self.maybe_play_ai_turn();
+
         Ok(())
     }
 
@@ -342,11 +830,11 @@ impl Game {
         let x = self
             .players
             .get_mut(&Shape::X)
-            .ok_or(GameError::IllegalState)? as *mut User;
+            .ok_or(GameError::IllegalState)? as *mut PlayerInfo;
         let o = self
             .players
             .get_mut(&Shape::O)
-            .ok_or(GameError::IllegalState)? as *mut User;
+            .ok_or(GameError::IllegalState)? as *mut PlayerInfo;
         unsafe {
             std::ptr::swap(x, o);
         }
@@ -354,13 +842,22 @@ impl Game {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub enum GameState {
     Waiting,
+    PendingAccept { challenger: PlayerInfo },
     Turn(Shape),
     Finished(GameResult),
 }
 
-#[derive(Clone)]
+fn other_shape(shape: Shape) -> Shape {
+    match shape {
+        Shape::X => Shape::O,
+        Shape::O => Shape::X,
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum GameResult {
     Victory { winner: Shape },
     Draw,
@@ -0,0 +1,81 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use color_eyre::eyre::{eyre, Result};
+
+use crate::game::Game;
+
+/// Pluggable persistence for the game registry, so deploys and crashes don't
+/// lose every in-progress match.
+#[async_trait::async_trait]
+pub trait GameStore: Send + Sync {
+    async fn save(&self, key: &str, game: &Game) -> Result<()>;
+    async fn remove(&self, key: &str) -> Result<()>;
+    async fn load_all(&self) -> Result<HashMap<String, Game>>;
+}
+
+/// Snapshots each game to its own CBOR file, named after its registry key.
+pub struct FileGameStore {
+    dir: PathBuf,
+}
+
+impl FileGameStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    // Rejects anything that isn't a plain path segment, so a key can never escape
+    // `self.dir` via a `/` or `..` component.
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(eyre!("invalid game key: {key:?}"));
+        }
+
+        Ok(self.dir.join(format!("{key}.cbor")))
+    }
+}
+
+#[async_trait::async_trait]
+impl GameStore for FileGameStore {
+    async fn save(&self, key: &str, game: &Game) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let bytes = serde_cbor::to_vec(game)?;
+        tokio::fs::write(self.path_for(key)?, bytes).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn load_all(&self) -> Result<HashMap<String, Game>> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let mut games = HashMap::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("cbor") {
+                continue;
+            }
+
+            let Some(key) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let bytes = tokio::fs::read(&path).await?;
+            match serde_cbor::from_slice::<Game>(&bytes) {
+                Ok(game) => {
+                    games.insert(key.to_string(), game);
+                }
+                Err(error) => log::warn!("Skipping corrupt saved game {key}: {error}"),
+            }
+        }
+
+        Ok(games)
+    }
+}
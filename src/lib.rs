@@ -0,0 +1,5 @@
+pub mod bot;
+pub mod callback_data;
+pub mod concurrent_hash_map;
+pub mod game;
+pub mod game_store;